@@ -1,4 +1,7 @@
 use super::{conv, Error, ExpectedToken, Span, Token, TokenSpan};
+use std::cell::OnceCell;
+use std::ops::Range;
+use std::rc::Rc;
 
 fn _consume_str<'a>(input: &'a str, what: &str) -> Option<&'a str> {
     if input.starts_with(what) {
@@ -8,52 +11,399 @@ fn _consume_str<'a>(input: &'a str, what: &str) -> Option<&'a str> {
     }
 }
 
+/// A WGSL reserved keyword.
+///
+/// Every identifier-shaped lexeme is matched against this set; a hit becomes a
+/// [`Token::Keyword`] so the parser can branch on a variant instead of string
+/// comparisons, and so reserved words cannot be silently used as identifiers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Keyword {
+    Alias,
+    Break,
+    Case,
+    Const,
+    ConstAssert,
+    Continue,
+    Continuing,
+    Default,
+    Discard,
+    Else,
+    Enable,
+    False,
+    Fn,
+    For,
+    If,
+    Let,
+    Loop,
+    Override,
+    Requires,
+    Return,
+    Struct,
+    Switch,
+    True,
+    Var,
+    While,
+}
+
+impl Keyword {
+    /// Classifies an identifier lexeme, returning the matching keyword or `None`
+    /// if the word is an ordinary identifier.
+    fn from_ident(word: &str) -> Option<Keyword> {
+        Some(match word {
+            "alias" => Keyword::Alias,
+            "break" => Keyword::Break,
+            "case" => Keyword::Case,
+            "const" => Keyword::Const,
+            "const_assert" => Keyword::ConstAssert,
+            "continue" => Keyword::Continue,
+            "continuing" => Keyword::Continuing,
+            "default" => Keyword::Default,
+            "discard" => Keyword::Discard,
+            "else" => Keyword::Else,
+            "enable" => Keyword::Enable,
+            "false" => Keyword::False,
+            "fn" => Keyword::Fn,
+            "for" => Keyword::For,
+            "if" => Keyword::If,
+            "let" => Keyword::Let,
+            "loop" => Keyword::Loop,
+            "override" => Keyword::Override,
+            "requires" => Keyword::Requires,
+            "return" => Keyword::Return,
+            "struct" => Keyword::Struct,
+            "switch" => Keyword::Switch,
+            "true" => Keyword::True,
+            "var" => Keyword::Var,
+            "while" => Keyword::While,
+            _ => return None,
+        })
+    }
+}
+
+/// Drops a `0x`/`0X` prefix from a hex literal so the remaining digits can be
+/// fed to `from_str_radix`; decimal literals are returned unchanged.
+fn strip_radix_prefix(value: &str, radix: u32) -> &str {
+    if radix == 16 {
+        value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .unwrap_or(value)
+    } else {
+        value
+    }
+}
+
+/// Is `word` one of WGSL's future-reserved words — reserved for later use and
+/// therefore forbidden as an identifier, though it is not an active [`Keyword`].
+///
+/// Reporting these up front lets `next_ident` reject them with a clear error
+/// rather than silently accepting them as identifiers.
+fn is_reserved_word(word: &str) -> bool {
+    matches!(
+        word,
+        "NULL"
+            | "Self"
+            | "abstract"
+            | "active"
+            | "alignas"
+            | "alignof"
+            | "as"
+            | "asm"
+            | "asm_fragment"
+            | "async"
+            | "attribute"
+            | "auto"
+            | "await"
+            | "become"
+            | "binding_array"
+            | "cast"
+            | "catch"
+            | "class"
+            | "co_await"
+            | "co_return"
+            | "co_yield"
+            | "coherent"
+            | "column_major"
+            | "common"
+            | "compile"
+            | "compile_fragment"
+            | "concept"
+            | "const_cast"
+            | "consteval"
+            | "constexpr"
+            | "constinit"
+            | "crate"
+            | "debugger"
+            | "decltype"
+            | "delete"
+            | "demote"
+            | "demote_to_helper"
+            | "do"
+            | "dynamic_cast"
+            | "enum"
+            | "explicit"
+            | "export"
+            | "extends"
+            | "extern"
+            | "external"
+            | "fallthrough"
+            | "filter"
+            | "final"
+            | "finally"
+            | "friend"
+            | "from"
+            | "fxgroup"
+            | "get"
+            | "goto"
+            | "groupshared"
+            | "highp"
+            | "impl"
+            | "implements"
+            | "import"
+            | "inline"
+            | "instanceof"
+            | "interface"
+            | "layout"
+            | "lowp"
+            | "macro"
+            | "macro_rules"
+            | "match"
+            | "mediump"
+            | "meta"
+            | "mod"
+            | "module"
+            | "move"
+            | "mut"
+            | "mutable"
+            | "namespace"
+            | "new"
+            | "nil"
+            | "noexcept"
+            | "noinline"
+            | "nointerpolation"
+            | "non_coherent"
+            | "noncoherent"
+            | "noperspective"
+            | "null"
+            | "nullptr"
+            | "of"
+            | "operator"
+            | "package"
+            | "packoffset"
+            | "partition"
+            | "pass"
+            | "patch"
+            | "pixelfragment"
+            | "precise"
+            | "precision"
+            | "premerge"
+            | "priv"
+            | "protected"
+            | "pub"
+            | "public"
+            | "readonly"
+            | "ref"
+            | "regardless"
+            | "register"
+            | "reinterpret_cast"
+            | "require"
+            | "resource"
+            | "restrict"
+            | "self"
+            | "set"
+            | "shared"
+            | "sizeof"
+            | "smooth"
+            | "snorm"
+            | "static"
+            | "static_assert"
+            | "static_cast"
+            | "std"
+            | "subroutine"
+            | "super"
+            | "target"
+            | "template"
+            | "this"
+            | "thread_local"
+            | "throw"
+            | "trait"
+            | "try"
+            | "type"
+            | "typedef"
+            | "typeid"
+            | "typename"
+            | "typeof"
+            | "union"
+            | "unless"
+            | "unorm"
+            | "unsafe"
+            | "unsized"
+            | "use"
+            | "using"
+            | "varying"
+            | "virtual"
+            | "volatile"
+            | "wgsl"
+            | "where"
+            | "with"
+            | "writeonly"
+            | "yield"
+    )
+}
+
 fn consume_any(input: &str, what: impl Fn(char) -> bool) -> (&str, &str) {
     let pos = input.find(|c| !what(c)).unwrap_or_else(|| input.len());
     input.split_at(pos)
 }
 
-fn consume_number(input: &str) -> (Token, &str) {
-    //Note: I wish this function was simpler and faster...
-    let mut is_first_char = true;
-    let mut right_after_exponent = false;
-
-    let mut what = |c| {
-        if is_first_char {
-            is_first_char = false;
-            c == '-' || ('0'..='9').contains(&c) || c == '.'
-        } else if c == 'e' || c == 'E' {
-            right_after_exponent = true;
-            true
-        } else if right_after_exponent {
-            right_after_exponent = false;
-            ('0'..='9').contains(&c) || c == '-'
-        } else {
-            ('0'..='9').contains(&c) || c == '.'
+/// Lexes a WGSL numeric literal, following the grammar's four shapes: a decimal
+/// integer, a decimal float (fractional part and/or decimal exponent), a hex
+/// integer, and a hex float (hex mantissa with a `.` and/or a binary `p`/`P`
+/// exponent). The recognised radix and optional suffix (`i`/`u` on integers,
+/// `f`/`h` on floats) are emitted in [`Token::Number`] so the parser can build
+/// the correct abstract-int or abstract-float value.
+fn consume_number(input: &str) -> (Token<'_>, &str) {
+    let bytes = input.as_bytes();
+    let len = bytes.len();
+    let mut i = 0;
+
+    // A leading `-` is only reached from `consume_token` when a digit or `.`
+    // follows, so it always begins a number here.
+    if i < len && bytes[i] == b'-' {
+        i += 1;
+    }
+
+    let mut radix = 10;
+    let mut is_float = false;
+
+    let number_err = |message: &str, end: usize| {
+        (
+            Token::Error {
+                message: message.to_string(),
+                span: 0..end,
+            },
+            &input[end..],
+        )
+    };
+
+    if i + 1 < len && bytes[i] == b'0' && matches!(bytes[i + 1], b'x' | b'X') {
+        radix = 16;
+        i += 2;
+        let mut saw_digit = false;
+        while i < len && bytes[i].is_ascii_hexdigit() {
+            i += 1;
+            saw_digit = true;
+        }
+        let mut has_dot = false;
+        if i < len && bytes[i] == b'.' {
+            has_dot = true;
+            i += 1;
+            while i < len && bytes[i].is_ascii_hexdigit() {
+                i += 1;
+                saw_digit = true;
+            }
+        }
+        // WGSL requires at least one hex digit in the mantissa, so `0x`, `0x.`,
+        // and `0x.p0` are errors, not `0` followed by `x`.
+        if !saw_digit {
+            return number_err("expected hexadecimal digits after `0x`", i);
+        }
+        let mut has_exponent = false;
+        if i < len && matches!(bytes[i], b'p' | b'P') {
+            has_exponent = true;
+            i += 1;
+            if i < len && matches!(bytes[i], b'+' | b'-') {
+                i += 1;
+            }
+            let exponent_start = i;
+            while i < len && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            if i == exponent_start {
+                return number_err("expected digits in hexadecimal float exponent", i);
+            }
+        }
+        // A hex literal is a float exactly when it has a `.` or a `p` exponent.
+        is_float = has_dot || has_exponent;
+    } else {
+        while i < len && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i < len && bytes[i] == b'.' {
+            is_float = true;
+            i += 1;
+            while i < len && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+        if i < len && matches!(bytes[i], b'e' | b'E') {
+            is_float = true;
+            i += 1;
+            if i < len && matches!(bytes[i], b'+' | b'-') {
+                i += 1;
+            }
+            let exponent_start = i;
+            while i < len && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            if i == exponent_start {
+                return number_err("expected digits in float exponent", i);
+            }
+        }
+    }
+
+    let value = &input[..i];
+    let suffix = match bytes.get(i).copied() {
+        Some(c @ (b'i' | b'u')) if !is_float => {
+            i += 1;
+            Some(c as char)
+        }
+        Some(c @ (b'f' | b'h')) => {
+            i += 1;
+            Some(c as char)
         }
+        _ => None,
     };
-    let pos = input.find(|c| !what(c)).unwrap_or_else(|| input.len());
-    let (value, rest) = input.split_at(pos);
-
-    let mut rest_iter = rest.chars();
-    let ty = rest_iter.next().unwrap_or(' ');
-    match ty {
-        'u' | 'i' | 'f' => {
-            let width_end = rest_iter
-                .position(|c| !('0'..='9').contains(&c))
-                .unwrap_or_else(|| rest.len() - 1);
-            let (width, rest) = rest[1..].split_at(width_end);
-            (Token::Number { value, ty, width }, rest)
-        }
-        // default to `i32` or `f32`
-        _ => (
-            Token::Number {
-                value,
-                ty: if value.contains('.') { 'f' } else { 'i' },
-                width: "",
-            },
-            rest,
-        ),
+
+    (
+        Token::Number {
+            value,
+            radix,
+            suffix,
+        },
+        &input[i..],
+    )
+}
+
+/// Is `c` a plausible token boundary to resume lexing at after a lexical error?
+fn is_recovery_boundary(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ';' | ',' | ':' | '(' | ')' | '{' | '}' | '[' | ']')
+}
+
+/// A single-token variant of [`consume_token`] for error-recovery mode.
+///
+/// It runs the normal scanner and, for the tokens that signal a lexical error
+/// ([`Token::Unknown`], [`Token::UnterminatedString`] and a malformed number's
+/// [`Token::Error`]), returns an `Err` carrying a human-readable message and
+/// advances `input` past the bad span up to the next plausible token boundary,
+/// so a single pass can surface several errors instead of aborting on the first.
+fn consume_token_recover(input: &str) -> (Result<Token<'_>, String>, &str) {
+    let (token, rest) = consume_token(input, false);
+    match token {
+        Token::Unknown(c) => {
+            let (_, rest) = consume_any(rest, |c| !is_recovery_boundary(c));
+            (Err(format!("unexpected character {c:?}")), rest)
+        }
+        Token::UnterminatedString => {
+            let (_, rest) = consume_any(rest, |c| !is_recovery_boundary(c));
+            (Err("unterminated string literal".to_string()), rest)
+        }
+        // A malformed number already carries its own message.
+        Token::Error { message, .. } => {
+            let (_, rest) = consume_any(rest, |c| !is_recovery_boundary(c));
+            (Err(message), rest)
+        }
+        other => (Ok(other), rest),
     }
 }
 
@@ -103,7 +453,10 @@ fn consume_token(mut input: &str, generic: bool) -> (Token<'_>, &str) {
         '0'..='9' => consume_number(input),
         'a'..='z' | 'A'..='Z' | '_' => {
             let (word, rest) = consume_any(input, |c| c.is_ascii_alphanumeric() || c == '_');
-            (Token::Word(word), rest)
+            match Keyword::from_ident(word) {
+                Some(keyword) => (Token::Keyword(keyword), rest),
+                None => (Token::Word(word), rest),
+            }
         }
         '"' => {
             let mut iter = chars.as_str().splitn(2, '"');
@@ -153,10 +506,83 @@ fn consume_token(mut input: &str, generic: bool) -> (Token<'_>, &str) {
     }
 }
 
+/// A human-readable source location: a 1-based line and a 1-based column.
+///
+/// Columns count Unicode scalar values rather than bytes, so a span starting at
+/// a multibyte identifier reports the column a reader would actually point at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct Location {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Lazily-built map from byte offsets into the source to `(line, column)`
+/// [`Location`]s.
+///
+/// The table of line-start byte offsets is scanned once, on the first call to
+/// [`resolve`](SourceMap::resolve), and cached for the lifetime of the map; a
+/// byte offset is translated by binary-searching that table for the greatest
+/// line start that does not exceed it. This mirrors the fallback source map in
+/// `proc-macro2`, and lets diagnostics render `line:col` without every consumer
+/// re-scanning the whole file.
+#[derive(Clone)]
+pub(super) struct SourceMap<'a> {
+    source: &'a str,
+    line_starts: OnceCell<Vec<usize>>,
+}
+
+impl<'a> SourceMap<'a> {
+    fn new(source: &'a str) -> Self {
+        SourceMap {
+            source,
+            line_starts: OnceCell::new(),
+        }
+    }
+
+    /// Byte offset of the first character of each line, in ascending order.
+    ///
+    /// The first entry is always `0`; every subsequent entry is the offset just
+    /// past a `\n`.
+    fn line_starts(&self) -> &[usize] {
+        self.line_starts.get_or_init(|| {
+            let mut starts = vec![0];
+            starts.extend(self.source.match_indices('\n').map(|(i, _)| i + 1));
+            starts
+        })
+    }
+
+    fn location(&self, offset: usize) -> Location {
+        let starts = self.line_starts();
+        // The line containing `offset` is the last one whose start is `<=` it.
+        let line = starts.partition_point(|&start| start <= offset) - 1;
+        let column = self.source[starts[line]..offset].chars().count();
+        Location {
+            line: line as u32 + 1,
+            column: column as u32 + 1,
+        }
+    }
+
+    /// Resolves a byte-offset span to a range of `(line, column)` locations.
+    pub(super) fn resolve(&self, span: Span) -> Range<Location> {
+        self.location(span.start)..self.location(span.end)
+    }
+}
+
+/// A lexical error collected while lexing in error-recovery mode.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+}
+
 #[derive(Clone)]
 pub(super) struct Lexer<'a> {
     input: &'a str,
     pub(super) source: &'a str,
+    // Shared behind an `Rc` so the frequent `peek` clones don't copy the
+    // (lazily-built) line-start table.
+    source_map: Rc<SourceMap<'a>>,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl<'a> Lexer<'a> {
@@ -164,9 +590,18 @@ impl<'a> Lexer<'a> {
         Lexer {
             input,
             source: input,
+            source_map: Rc::new(SourceMap::new(input)),
+            diagnostics: Vec::new(),
         }
     }
 
+    /// Resolves a byte-offset span to a range of human-readable
+    /// `(line, column)` [`Location`]s, building the line-start table on first
+    /// use. See [`SourceMap`].
+    pub(super) fn resolve_span(&self, span: Span) -> Range<Location> {
+        self.source_map.resolve(span)
+    }
+
     pub(super) fn _leftover_span(&self) -> Span {
         self.source.len() - self.input.len()..self.source.len()
     }
@@ -209,11 +644,64 @@ impl<'a> Lexer<'a> {
             self.input = rest;
             match token {
                 Token::Trivia => start_byte_offset = self.current_byte_offset(),
+                // A malformed number carries a placeholder span; rebase it onto
+                // the actual byte range it covers.
+                Token::Error { message, .. } => {
+                    let span = start_byte_offset..self.current_byte_offset();
+                    return (
+                        Token::Error {
+                            message,
+                            span: span.clone(),
+                        },
+                        span,
+                    );
+                }
                 _ => return (token, start_byte_offset..self.current_byte_offset()),
             }
         }
     }
 
+    /// Error-recovery counterpart of [`next`](Self::next).
+    ///
+    /// On an illegal character, unterminated string, or malformed number it
+    /// records a [`Diagnostic`], returns a `Token::Error { message, span }`, and
+    /// leaves the input positioned past the bad span so the next call resumes at
+    /// a plausible token boundary. Collected errors are drained with
+    /// [`take_diagnostics`](Self::take_diagnostics). The non-recovering
+    /// [`next`](Self::next) records no diagnostics.
+    #[must_use]
+    pub(super) fn next_recover(&mut self) -> TokenSpan<'a> {
+        let mut start_byte_offset = self.current_byte_offset();
+        loop {
+            let (result, rest) = consume_token_recover(self.input);
+            self.input = rest;
+            match result {
+                Ok(Token::Trivia) => start_byte_offset = self.current_byte_offset(),
+                Ok(token) => return (token, start_byte_offset..self.current_byte_offset()),
+                Err(message) => {
+                    let span = start_byte_offset..self.current_byte_offset();
+                    self.diagnostics.push(Diagnostic {
+                        message: message.clone(),
+                        span: span.clone(),
+                    });
+                    return (
+                        Token::Error {
+                            message,
+                            span: span.clone(),
+                        },
+                        span,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Drains and returns every lexical error collected by
+    /// [`next_recover`](Self::next_recover) so far.
+    pub(super) fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
     #[must_use]
     pub(super) fn next_generic(&mut self) -> TokenSpan<'a> {
         let mut start_byte_offset = self.current_byte_offset();
@@ -222,6 +710,16 @@ impl<'a> Lexer<'a> {
             self.input = rest;
             match token {
                 Token::Trivia => start_byte_offset = self.current_byte_offset(),
+                Token::Error { message, .. } => {
+                    let span = start_byte_offset..self.current_byte_offset();
+                    return (
+                        Token::Error {
+                            message,
+                            span: span.clone(),
+                        },
+                        span,
+                    );
+                }
                 _ => return (token, start_byte_offset..self.current_byte_offset()),
             }
         }
@@ -250,6 +748,26 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
+    /// Expects the next token to be `keyword`, erroring otherwise. Analogous to
+    /// [`expect`](Self::expect).
+    pub(super) fn expect_keyword(&mut self, keyword: Keyword) -> Result<(), Error<'a>> {
+        let next = self.next();
+        if next.0 == Token::Keyword(keyword) {
+            Ok(())
+        } else {
+            Err(Error::Unexpected(
+                next,
+                ExpectedToken::Token(Token::Keyword(keyword)),
+            ))
+        }
+    }
+
+    /// If the next token is `keyword` it is skipped and `true` is returned.
+    /// Analogous to [`skip`](Self::skip).
+    pub(super) fn skip_keyword(&mut self, keyword: Keyword) -> bool {
+        self.skip(Token::Keyword(keyword))
+    }
+
     pub(super) fn expect_generic_paren(&mut self, expected: char) -> Result<(), Error<'a>> {
         let next = self.next_generic();
         if next.0 == Token::Paren(expected) {
@@ -275,18 +793,29 @@ impl<'a> Lexer<'a> {
 
     pub(super) fn next_ident_with_span(&mut self) -> Result<(&'a str, Span), Error<'a>> {
         match self.next() {
+            (Token::Word(word), span) if is_reserved_word(word) => {
+                Err(Error::ReservedKeyword(span))
+            }
             (Token::Word(word), span) => Ok((word, span)),
+            (Token::Keyword(_), span) => Err(Error::ReservedKeyword(span)),
             other => Err(Error::Unexpected(other, ExpectedToken::Identifier)),
         }
     }
 
     pub(super) fn next_ident(&mut self) -> Result<&'a str, Error<'a>> {
         match self.next() {
+            (Token::Word(word), span) if is_reserved_word(word) => {
+                Err(Error::ReservedKeyword(span))
+            }
             (Token::Word(word), _) => Ok(word),
+            (Token::Keyword(_), span) => Err(Error::ReservedKeyword(span)),
             other => Err(Error::Unexpected(other, ExpectedToken::Identifier)),
         }
     }
 
+    /// Parses a decimal float literal. Hex floats (`radix == 16`) cannot be
+    /// parsed by `str::parse` and are value-built by the parser from the emitted
+    /// radix and suffix; this helper only handles the decimal form.
     fn _next_float_literal(&mut self) -> Result<f32, Error<'a>> {
         match self.next() {
             (Token::Number { value, .. }, span) => {
@@ -298,9 +827,9 @@ impl<'a> Lexer<'a> {
 
     pub(super) fn next_uint_literal(&mut self) -> Result<u32, Error<'a>> {
         match self.next() {
-            (Token::Number { value, .. }, span) => {
-                let v = value.parse();
-                v.map_err(|e| Error::BadU32(span, e))
+            (Token::Number { value, radix, .. }, span) => {
+                u32::from_str_radix(strip_radix_prefix(value, radix), radix)
+                    .map_err(|e| Error::BadU32(span, e))
             }
             other => Err(Error::Unexpected(other, ExpectedToken::Uint)),
         }
@@ -308,8 +837,23 @@ impl<'a> Lexer<'a> {
 
     pub(super) fn next_sint_literal(&mut self) -> Result<i32, Error<'a>> {
         match self.next() {
-            (Token::Number { value, .. }, span) => {
-                value.parse().map_err(|e| Error::BadI32(span, e))
+            (Token::Number { value, radix, .. }, span) => {
+                let parsed = if radix == 16 {
+                    // `from_str_radix` accepts a leading sign, but the `0x`
+                    // prefix sits between the sign and the digits, so split the
+                    // sign off first and re-apply it to the parsed magnitude.
+                    let (negative, digits) = match value.strip_prefix('-') {
+                        Some(rest) => (true, rest),
+                        None => (false, value),
+                    };
+                    i32::from_str_radix(strip_radix_prefix(digits, radix), radix)
+                        .map(|v| if negative { -v } else { v })
+                } else {
+                    // Decimal: pass the signed value straight through so
+                    // `i32::MIN` parses without an intermediate overflow.
+                    i32::from_str_radix(value, radix)
+                };
+                parsed.map_err(|e| Error::BadI32(span, e))
             }
             other => Err(Error::Unexpected(other, ExpectedToken::Sint)),
         }
@@ -374,6 +918,177 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// A rope-backed lexer that re-tokenizes only the region touched by an edit,
+/// for interactive use by a WGSL language server.
+///
+/// Unlike [`Lexer`], which borrows a single `&str` and so has to be rebuilt
+/// over the whole new source after an edit, `IncrementalLexer` owns a [`ropey::Rope`] and keeps a sorted
+/// side table of the tokens it last produced. An edit re-lexes from the start of
+/// the token overlapping the edit and stops as soon as the stream
+/// re-synchronizes with the recorded tokens past the edit, so untouched trailing
+/// tokens are reused with their spans merely shifted by the length delta.
+///
+/// Because the token text lives in the rope rather than in a borrowed slice, the
+/// recorded tokens own their lexemes (see [`RecordedToken`]) instead of the
+/// borrowing [`TokenSpan`] the non-incremental path uses.
+#[cfg(feature = "ropey")]
+pub(super) struct IncrementalLexer {
+    rope: ropey::Rope,
+    tokens: Vec<RecordedToken>,
+}
+
+/// A token recorded in an [`IncrementalLexer`]'s side table.
+///
+/// It owns its lexeme so that it survives edits to the underlying rope, and so
+/// that a re-lexed token can be compared against it to detect re-synchronization.
+#[cfg(feature = "ropey")]
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct RecordedToken {
+    /// The verbatim source text of the token.
+    pub text: String,
+    /// The token's byte range in the current rope.
+    pub span: Span,
+}
+
+#[cfg(feature = "ropey")]
+impl IncrementalLexer {
+    pub(super) fn new(source: &str) -> Self {
+        // The initial tokenization is an unavoidable full O(n) pass; only
+        // subsequent `apply_edit` calls are incremental. Lex `source` directly
+        // rather than round-tripping it through the rope.
+        let tokens = Self::lex_all(source, 0);
+        let rope = ropey::Rope::from_str(source);
+        IncrementalLexer { rope, tokens }
+    }
+
+    /// Lexes `text` in full, returning one [`RecordedToken`] per significant
+    /// token with spans offset by `base` (the byte position of `text` within
+    /// the rope). Trivia and the terminal `End` are dropped.
+    fn lex_all(text: &str, base: usize) -> Vec<RecordedToken> {
+        let mut out = Vec::new();
+        let mut rest = text;
+        loop {
+            let start = base + (text.len() - rest.len());
+            let (token, after) = consume_token(rest, false);
+            if token == Token::End {
+                break;
+            }
+            let end = base + (text.len() - after.len());
+            if token != Token::Trivia {
+                out.push(RecordedToken {
+                    text: text[start - base..end - base].to_string(),
+                    span: start..end,
+                });
+            }
+            rest = after;
+        }
+        out
+    }
+
+    /// Applies an edit replacing the byte range `range` with `new_text` and
+    /// returns the tokens re-lexed between the resume point and the point of
+    /// re-synchronization; a re-lexed token can equal the one it replaces.
+    ///
+    /// Re-lexing resumes at the boundary of the first recorded token that
+    /// overlaps `range.start` — never mid-token — and stops once a freshly
+    /// lexed token matches, in both kind/text and relative span, a token that
+    /// was recorded past the edit, which guarantees the remaining tail is
+    /// reusable. Trailing recorded tokens are kept, their offsets shifted by the
+    /// length delta.
+    pub(super) fn apply_edit(&mut self, range: Span, new_text: &str) -> Vec<RecordedToken> {
+        let delta = new_text.len() as isize - (range.end - range.start) as isize;
+
+        // Resume at the start of the first token that reaches the edit. Using
+        // `>=` (not `>`) backs up to include a token ending exactly at the edit
+        // start, so deleting the separation between two tokens re-lexes the
+        // merged token instead of leaving the left one stranded.
+        let first = self
+            .tokens
+            .iter()
+            .position(|t| t.span.end >= range.start)
+            .unwrap_or(self.tokens.len());
+        let resume = match self.tokens.get(first) {
+            Some(t) if t.span.start <= range.start => t.span.start,
+            // The edit starts in trivia or past the last token: back up to the
+            // end of the previous token, the nearest known token boundary.
+            _ => first.checked_sub(1).map_or(0, |i| self.tokens[i].span.end),
+        };
+
+        // The first recorded token that is strictly past the edit is where we
+        // hope to re-synchronize; its post-edit span is shifted by `delta`.
+        let tail_start = self
+            .tokens
+            .iter()
+            .position(|t| t.span.start >= range.end)
+            .unwrap_or(self.tokens.len());
+
+        let char_start = self.rope.byte_to_char(range.start);
+        let char_end = self.rope.byte_to_char(range.end);
+        self.rope.remove(char_start..char_end);
+        self.rope.insert(char_start, new_text);
+
+        // Materialize only the rope from the resume point onward — everything
+        // before `resume` is unchanged by the edit — so the work is O(file
+        // length − resume) rather than O(file length) per keystroke. Offsets
+        // within `tail_text` are rebased to absolute positions by adding
+        // `resume`.
+        let tail_text = self.rope.byte_slice(resume..).to_string();
+        let mut rest = tail_text.as_str();
+        let mut changed = Vec::new();
+        let mut sync = tail_start;
+        loop {
+            let start = resume + (tail_text.len() - rest.len());
+            let (token, after) = consume_token(rest, false);
+            if token == Token::End {
+                sync = self.tokens.len();
+                break;
+            }
+            let end = resume + (tail_text.len() - after.len());
+            rest = after;
+            if token == Token::Trivia {
+                continue;
+            }
+            let text = tail_text[start - resume..end - resume].to_string();
+
+            // Re-synchronized: this token matches a recorded token past the
+            // edit (after shifting its span), so the rest of the tail is reused.
+            if let Some(found) = self.tokens[tail_start..].iter().position(|t| {
+                let shifted = (t.span.start as isize + delta) as usize;
+                shifted == start && t.text == text
+            }) {
+                sync = tail_start + found;
+                break;
+            }
+
+            changed.push(RecordedToken {
+                text,
+                span: start..end,
+            });
+        }
+
+        // Shift the reused tail and splice the rebuilt prefix in.
+        let mut tail: Vec<RecordedToken> = self.tokens[sync..]
+            .iter()
+            .map(|t| RecordedToken {
+                text: t.text.clone(),
+                span: (t.span.start as isize + delta) as usize
+                    ..(t.span.end as isize + delta) as usize,
+            })
+            .collect();
+        let mut rebuilt = self.tokens[..first].to_vec();
+        rebuilt.extend(changed.iter().cloned());
+        rebuilt.append(&mut tail);
+        self.tokens = rebuilt;
+
+        changed
+    }
+
+    /// The current token side table, in source order.
+    pub(super) fn tokens(&self) -> &[RecordedToken] {
+        &self.tokens
+    }
+}
+
 #[cfg(test)]
 fn sub_test(source: &str, expected_tokens: &[Token]) {
     let mut lex = Lexer::new(source);
@@ -391,8 +1106,8 @@ fn test_tokens() {
         &[
             Token::Number {
                 value: "92",
-                ty: 'i',
-                width: "",
+                radix: 10,
+                suffix: None,
             },
             Token::Word("No"),
         ],
@@ -402,8 +1117,13 @@ fn test_tokens() {
         &[
             Token::Number {
                 value: "2",
-                ty: 'u',
-                width: "3",
+                radix: 10,
+                suffix: Some('u'),
+            },
+            Token::Number {
+                value: "3",
+                radix: 10,
+                suffix: None,
             },
             Token::Word("o"),
         ],
@@ -413,12 +1133,42 @@ fn test_tokens() {
         &[
             Token::Number {
                 value: "2.4",
-                ty: 'f',
-                width: "44",
+                radix: 10,
+                suffix: Some('f'),
+            },
+            Token::Number {
+                value: "44",
+                radix: 10,
+                suffix: None,
             },
             Token::Word("po"),
         ],
     );
+    // Hex integers and floats, including the `h` (f16) suffix.
+    sub_test(
+        "0xFF",
+        &[Token::Number {
+            value: "0xFF",
+            radix: 16,
+            suffix: None,
+        }],
+    );
+    sub_test(
+        "0x1.8p3h",
+        &[Token::Number {
+            value: "0x1.8p3",
+            radix: 16,
+            suffix: Some('h'),
+        }],
+    );
+    sub_test(
+        "1.0E-3",
+        &[Token::Number {
+            value: "1.0E-3",
+            radix: 10,
+            suffix: None,
+        }],
+    );
     sub_test(
         "æNoø",
         &[Token::Unknown('æ'), Token::Word("No"), Token::Unknown('ø')],
@@ -438,12 +1188,12 @@ fn test_variable_decl() {
             Token::Paren('('),
             Token::Number {
                 value: "0",
-                ty: 'i',
-                width: "",
+                radix: 10,
+                suffix: None,
             },
             Token::Paren(')'),
             Token::DoubleParen(']'),
-            Token::Word("var"),
+            Token::Keyword(Keyword::Var),
             Token::Paren('<'),
             Token::Word("uniform"),
             Token::Paren('>'),
@@ -457,3 +1207,52 @@ fn test_variable_decl() {
         ],
     )
 }
+
+#[test]
+fn test_keywords() {
+    // Reserved words become `Keyword` tokens; a non-reserved lexeme that merely
+    // contains a keyword stays an identifier.
+    sub_test(
+        "fn main",
+        &[Token::Keyword(Keyword::Fn), Token::Word("main")],
+    );
+    sub_test("function", &[Token::Word("function")]);
+    sub_test(
+        "let x",
+        &[Token::Keyword(Keyword::Let), Token::Word("x")],
+    );
+}
+
+#[test]
+fn test_error_recovery() {
+    // Two illegal characters surrounding a valid identifier should yield two
+    // diagnostics rather than aborting on the first.
+    let mut lex = Lexer::new("æ No ¾");
+    let mut words = Vec::new();
+    loop {
+        match lex.next_recover() {
+            (Token::End, _) => break,
+            (Token::Word(w), _) => words.push(w),
+            _ => {}
+        }
+    }
+    assert_eq!(words, ["No"]);
+    assert_eq!(lex.take_diagnostics().len(), 2);
+}
+
+#[test]
+fn test_source_map() {
+    let lex = Lexer::new("let x =\n  æNo = 1;\n");
+    // `x` on the first line, column 5.
+    assert_eq!(
+        lex.resolve_span(4..5),
+        Location { line: 1, column: 5 }..Location { line: 1, column: 6 }
+    );
+    // `æNo` starts at column 3 of the second line; the column counts the
+    // multibyte `æ` as a single character, not its two bytes.
+    let off = lex.source.find("æNo").unwrap();
+    assert_eq!(
+        lex.resolve_span(off..off + "æNo".len()),
+        Location { line: 2, column: 3 }..Location { line: 2, column: 6 }
+    );
+}